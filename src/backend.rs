@@ -0,0 +1,211 @@
+use std::env;
+
+use async_trait::async_trait;
+use base64::engine::general_purpose;
+use base64::Engine;
+use serde_json::json;
+
+use crate::ModerationResult;
+
+/// A vision provider that can rate a single JPEG frame. Swapping providers is
+/// a matter of setting `MODERATION_BACKEND` rather than touching the actix
+/// handlers.
+#[async_trait]
+pub trait ModerationBackend: Send + Sync {
+    async fn moderate(&self, frame_bytes: &[u8]) -> Result<ModerationResult, Box<dyn std::error::Error>>;
+}
+
+const MODERATION_PROMPT: &str = "You are an image moderator. Analyze the image and return a JSON object with exactly two fields: 'description' (a concise analysis) and 'rating' (one of 'G', 'PG', 'PG-13', 'R', or 'Inappropriate'). Your response must be strictly valid JSON without any additional text.";
+
+fn parse_moderation_response(content: &str) -> ModerationResult {
+    let cleaned = if content.starts_with("```") {
+        content
+            .trim_start_matches("```json")
+            .trim_start_matches("```")
+            .trim_end_matches("```")
+            .trim()
+            .to_string()
+    } else {
+        content.trim().to_string()
+    };
+
+    serde_json::from_str(&cleaned).unwrap_or(ModerationResult {
+        description: format!("Parsing error in response: {}", cleaned),
+        rating: "Inappropriate".to_string(),
+    })
+}
+
+fn data_url(frame_bytes: &[u8]) -> String {
+    format!(
+        "data:image/jpeg;base64,{}",
+        general_purpose::STANDARD.encode(frame_bytes)
+    )
+}
+
+/// Reproduces today's behavior against x.ai's Grok vision endpoint.
+pub struct GrokBackend {
+    api_key: String,
+}
+
+impl GrokBackend {
+    pub fn from_env() -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self {
+            api_key: env::var("GROK_API_KEY")?,
+        })
+    }
+}
+
+#[async_trait]
+impl ModerationBackend for GrokBackend {
+    async fn moderate(&self, frame_bytes: &[u8]) -> Result<ModerationResult, Box<dyn std::error::Error>> {
+        let payload = json!({
+            "model": "grok-2-vision-latest",
+            "messages": [
+                { "role": "system", "content": MODERATION_PROMPT },
+                { "role": "user", "content": [{
+                    "type": "image_url",
+                    "image_url": { "url": data_url(frame_bytes), "detail": "high" }
+                }] },
+            ],
+            "temperature": 0.7
+        });
+
+        println!("Grok API Payload:\n{}", serde_json::to_string_pretty(&payload)?);
+
+        let client = reqwest::Client::new();
+        let resp = client
+            .post("https://api.x.ai/v1/chat/completions")
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .json(&payload)
+            .send()
+            .await?;
+
+        let json_resp: serde_json::Value = resp.json().await?;
+        println!("Grok API JSON response:\n{}", serde_json::to_string_pretty(&json_resp)?);
+
+        let content = json_resp["choices"][0]["message"]["content"]
+            .as_str()
+            .unwrap_or("")
+            .to_string();
+
+        let moderation = parse_moderation_response(&content);
+        println!("Cleaned moderation response:\n{:?}", moderation);
+
+        Ok(moderation)
+    }
+}
+
+/// Talks to any OpenAI-compatible `/chat/completions` endpoint (OpenAI itself,
+/// Azure OpenAI, vLLM, etc).
+pub struct OpenAiBackend {
+    api_key: String,
+    base_url: String,
+    model: String,
+}
+
+impl OpenAiBackend {
+    pub fn from_env() -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self {
+            api_key: env::var("OPENAI_API_KEY")?,
+            base_url: env::var("OPENAI_BASE_URL")
+                .unwrap_or_else(|_| "https://api.openai.com/v1".to_string()),
+            model: env::var("OPENAI_MODEL").unwrap_or_else(|_| "gpt-4o-mini".to_string()),
+        })
+    }
+}
+
+#[async_trait]
+impl ModerationBackend for OpenAiBackend {
+    async fn moderate(&self, frame_bytes: &[u8]) -> Result<ModerationResult, Box<dyn std::error::Error>> {
+        let payload = json!({
+            "model": self.model,
+            "messages": [
+                { "role": "system", "content": MODERATION_PROMPT },
+                { "role": "user", "content": [{
+                    "type": "image_url",
+                    "image_url": { "url": data_url(frame_bytes) }
+                }] },
+            ],
+            "temperature": 0.7
+        });
+
+        let client = reqwest::Client::new();
+        let resp = client
+            .post(format!("{}/chat/completions", self.base_url))
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .json(&payload)
+            .send()
+            .await?;
+
+        let json_resp: serde_json::Value = resp.json().await?;
+        let content = json_resp["choices"][0]["message"]["content"]
+            .as_str()
+            .unwrap_or("")
+            .to_string();
+
+        Ok(parse_moderation_response(&content))
+    }
+}
+
+/// Posts the raw JPEG to a self-hosted tagging service and maps its labels
+/// onto our rating scale, for operators who don't want to send frames to a
+/// third-party vision API at all.
+pub struct LocalClassifierBackend {
+    endpoint: String,
+}
+
+impl LocalClassifierBackend {
+    pub fn from_env() -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self {
+            endpoint: env::var("LOCAL_CLASSIFIER_URL")?,
+        })
+    }
+
+    fn rating_for_labels(labels: &[String]) -> &'static str {
+        if labels.iter().any(|l| l == "explicit") {
+            "Inappropriate"
+        } else if labels.iter().any(|l| l == "suggestive") {
+            "R"
+        } else if labels.iter().any(|l| l == "violence") {
+            "PG-13"
+        } else if labels.iter().any(|l| l == "mild") {
+            "PG"
+        } else {
+            "G"
+        }
+    }
+}
+
+#[async_trait]
+impl ModerationBackend for LocalClassifierBackend {
+    async fn moderate(&self, frame_bytes: &[u8]) -> Result<ModerationResult, Box<dyn std::error::Error>> {
+        let client = reqwest::Client::new();
+        let resp = client
+            .post(&self.endpoint)
+            .header("Content-Type", "image/jpeg")
+            .body(frame_bytes.to_vec())
+            .send()
+            .await?;
+
+        let json_resp: serde_json::Value = resp.json().await?;
+        let labels: Vec<String> = json_resp["labels"]
+            .as_array()
+            .map(|arr| arr.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+            .unwrap_or_default();
+
+        Ok(ModerationResult {
+            description: format!("Local classifier labels: {}", labels.join(", ")),
+            rating: Self::rating_for_labels(&labels).to_string(),
+        })
+    }
+}
+
+/// Selects the configured backend from `MODERATION_BACKEND` (`grok` by
+/// default, or `openai` / `local`).
+pub fn build_backend() -> Result<Box<dyn ModerationBackend>, Box<dyn std::error::Error>> {
+    match env::var("MODERATION_BACKEND").unwrap_or_else(|_| "grok".to_string()).as_str() {
+        "openai" => Ok(Box::new(OpenAiBackend::from_env()?)),
+        "local" => Ok(Box::new(LocalClassifierBackend::from_env()?)),
+        _ => Ok(Box::new(GrokBackend::from_env()?)),
+    }
+}
@@ -0,0 +1,72 @@
+use image::GenericImageView;
+
+/// Perceptual difference hash: downscale to 9x8 grayscale and compare each
+/// row's adjacent pixels, producing 8 bits per row for a 64-bit hash. Near
+/// identical frames land a small Hamming distance apart.
+pub fn compute(path: &str) -> Option<u64> {
+    let img = image::open(path).ok()?;
+    let small = img
+        .grayscale()
+        .resize_exact(9, 8, image::imageops::FilterType::Triangle);
+
+    let mut hash: u64 = 0;
+    for y in 0..8 {
+        for x in 0..8 {
+            let left = small.get_pixel(x, y)[0];
+            let right = small.get_pixel(x + 1, y)[0];
+            hash = (hash << 1) | u64::from(left > right);
+        }
+    }
+    Some(hash)
+}
+
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::write_gradient;
+
+    #[test]
+    fn hamming_distance_of_identical_hashes_is_zero() {
+        assert_eq!(hamming_distance(0b1010_1010, 0b1010_1010), 0);
+    }
+
+    #[test]
+    fn hamming_distance_counts_differing_bits() {
+        assert_eq!(hamming_distance(0b0000, 0b1111), 4);
+    }
+
+    #[test]
+    fn compute_returns_none_for_a_missing_file() {
+        assert!(compute("/nonexistent/path/to/frame.jpg").is_none());
+    }
+
+    #[test]
+    fn compute_is_stable_for_the_same_image() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.jpg");
+        write_gradient(&path, true);
+
+        let first = compute(path.to_str().unwrap()).unwrap();
+        let second = compute(path.to_str().unwrap()).unwrap();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn compute_distinguishes_visually_different_frames() {
+        let dir = tempfile::tempdir().unwrap();
+        let ascending = dir.path().join("ascending.jpg");
+        let descending = dir.path().join("descending.jpg");
+        write_gradient(&ascending, true);
+        write_gradient(&descending, false);
+
+        let hash_ascending = compute(ascending.to_str().unwrap()).unwrap();
+        let hash_descending = compute(descending.to_str().unwrap()).unwrap();
+
+        assert_ne!(hash_ascending, hash_descending);
+        assert!(hamming_distance(hash_ascending, hash_descending) > 32);
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Done,
+    Failed,
+}
+
+/// A failure reason distinguishable enough that `/jobs/{id}` can respond
+/// with something other than a blanket 200/500 — e.g. an oversized video
+/// surfaces as an HTTP 413 rather than an opaque error string.
+#[derive(Clone, Serialize)]
+pub struct JobError {
+    #[serde(skip)]
+    pub status: u16,
+    pub reason: &'static str,
+    pub message: String,
+}
+
+struct JobRecord {
+    status: JobStatus,
+    result: Option<Value>,
+    error: Option<JobError>,
+}
+
+pub type JobStore = Arc<Mutex<HashMap<Uuid, JobRecord>>>;
+
+#[derive(Serialize)]
+pub struct JobView {
+    pub job_id: Uuid,
+    pub status: JobStatus,
+    // The `{ "frames": [...], "summary": {...} }` object `process_video`
+    // produces, flattened so callers see `frames`/`summary` at the top level.
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<JobError>,
+}
+
+impl JobView {
+    /// The HTTP status `/jobs/{id}` should answer with: the failure's own
+    /// status when the job failed, 200 otherwise.
+    pub fn http_status(&self) -> u16 {
+        match (&self.status, &self.error) {
+            (JobStatus::Failed, Some(error)) => error.status,
+            (JobStatus::Failed, None) => 500,
+            _ => 200,
+        }
+    }
+}
+
+/// Handle shared between the `/moderate` and `/jobs/{id}` handlers and the
+/// background worker that drains the queue.
+#[derive(Clone)]
+pub struct JobQueue {
+    store: JobStore,
+    sender: mpsc::Sender<(Uuid, String)>,
+}
+
+impl JobQueue {
+    pub fn new(capacity: usize) -> (Self, mpsc::Receiver<(Uuid, String)>) {
+        let (sender, receiver) = mpsc::channel(capacity);
+        let queue = Self {
+            store: Arc::new(Mutex::new(HashMap::new())),
+            sender,
+        };
+        (queue, receiver)
+    }
+
+    /// Records the job as queued and hands the URL off to the worker task.
+    /// Returns `None` if the queue is full so the caller can reject the
+    /// request instead of blocking the HTTP handler on a full channel.
+    pub fn enqueue(&self, url: String) -> Option<Uuid> {
+        let job_id = Uuid::new_v4();
+        // Insert before handing off to the worker: the worker can start
+        // processing as soon as `try_send` succeeds, and `mark_running`/
+        // `mark_done` silently no-op if the record isn't there yet. Insert
+        // first so the record always exists before the job becomes visible
+        // to the worker, then roll it back if the channel turns out full.
+        self.store.lock().unwrap().insert(
+            job_id,
+            JobRecord {
+                status: JobStatus::Queued,
+                result: None,
+                error: None,
+            },
+        );
+        // `try_send` instead of `send().await`: with a single serial worker,
+        // an `await` here would block the handler itself once the queue
+        // fills up, reintroducing the held-open-connection problem this
+        // queue exists to avoid.
+        if self.sender.try_send((job_id, url)).is_err() {
+            self.store.lock().unwrap().remove(&job_id);
+            return None;
+        }
+        Some(job_id)
+    }
+
+    pub fn get(&self, job_id: &Uuid) -> Option<JobView> {
+        let store = self.store.lock().unwrap();
+        store.get(job_id).map(|record| JobView {
+            job_id: *job_id,
+            status: record.status,
+            result: record.result.clone(),
+            error: record.error.clone(),
+        })
+    }
+
+    pub fn mark_running(&self, job_id: Uuid) {
+        if let Some(record) = self.store.lock().unwrap().get_mut(&job_id) {
+            record.status = JobStatus::Running;
+        }
+    }
+
+    pub fn mark_done(&self, job_id: Uuid, result: Value) {
+        if let Some(record) = self.store.lock().unwrap().get_mut(&job_id) {
+            record.status = JobStatus::Done;
+            record.result = Some(result);
+        }
+    }
+
+    pub fn mark_failed(&self, job_id: Uuid, error: JobError) {
+        if let Some(record) = self.store.lock().unwrap().get_mut(&job_id) {
+            record.status = JobStatus::Failed;
+            record.error = Some(error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn enqueue_then_get_reports_queued() {
+        let (queue, _receiver) = JobQueue::new(10);
+        let job_id = queue.enqueue("http://example.com/a.mp4".to_string()).unwrap();
+
+        let view = queue.get(&job_id).unwrap();
+        assert_eq!(view.status, JobStatus::Queued);
+        assert!(view.result.is_none());
+        assert!(view.error.is_none());
+    }
+
+    #[test]
+    fn get_returns_none_for_an_unknown_job() {
+        let (queue, _receiver) = JobQueue::new(10);
+        assert!(queue.get(&Uuid::new_v4()).is_none());
+    }
+
+    #[test]
+    fn mark_running_transitions_from_queued() {
+        let (queue, _receiver) = JobQueue::new(10);
+        let job_id = queue.enqueue("http://example.com/a.mp4".to_string()).unwrap();
+
+        queue.mark_running(job_id);
+
+        assert_eq!(queue.get(&job_id).unwrap().status, JobStatus::Running);
+    }
+
+    #[test]
+    fn mark_done_stores_the_result() {
+        let (queue, _receiver) = JobQueue::new(10);
+        let job_id = queue.enqueue("http://example.com/a.mp4".to_string()).unwrap();
+
+        queue.mark_done(job_id, json!({ "frames": [] }));
+
+        let view = queue.get(&job_id).unwrap();
+        assert_eq!(view.status, JobStatus::Done);
+        assert_eq!(view.result, Some(json!({ "frames": [] })));
+    }
+
+    #[test]
+    fn mark_failed_surfaces_reason_and_status() {
+        let (queue, _receiver) = JobQueue::new(10);
+        let job_id = queue.enqueue("http://example.com/a.mp4".to_string()).unwrap();
+
+        queue.mark_failed(
+            job_id,
+            JobError {
+                status: 413,
+                reason: "payload_too_large",
+                message: "video exceeds the size limit".to_string(),
+            },
+        );
+
+        let view = queue.get(&job_id).unwrap();
+        assert_eq!(view.status, JobStatus::Failed);
+        assert_eq!(view.http_status(), 413);
+        assert_eq!(view.error.as_ref().unwrap().reason, "payload_too_large");
+    }
+
+    #[test]
+    fn enqueue_returns_none_when_the_queue_is_full() {
+        let (queue, _receiver) = JobQueue::new(1);
+        let first = queue.enqueue("http://example.com/a.mp4".to_string());
+        assert!(first.is_some());
+
+        let second = queue.enqueue("http://example.com/b.mp4".to_string());
+        assert!(second.is_none());
+
+        // The rejected job must not leave an orphaned record behind.
+        assert_eq!(queue.store.lock().unwrap().len(), 1);
+    }
+}
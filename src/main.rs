@@ -1,7 +1,14 @@
-use actix_web::{post, web, HttpRequest, HttpResponse, HttpServer, Responder};
-use base64::engine::general_purpose;
-use base64::Engine;
+mod backend;
+mod dhash;
+mod jobs;
+#[cfg(test)]
+mod test_support;
+
+use actix_web::{get, post, web, HttpRequest, HttpResponse, HttpServer, Responder};
+use backend::{build_backend, ModerationBackend};
 use dotenv::dotenv;
+use futures::future::join_all;
+use jobs::JobQueue;
 use reqwest;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -10,29 +17,146 @@ use std::fs as stdfs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 use tempfile::{NamedTempFile, TempDir};
-use url::Url;
+use tokio::sync::{mpsc, Semaphore};
 use tokio::time::sleep;
+use url::Url;
+use uuid::Uuid;
 
 #[derive(Deserialize)]
 struct RequestBody {
     url: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct ModerationResult {
-    description: String,
-    rating: String, // "G", "PG", "PG-13", "R", or "Inappropriate"
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct ModerationResult {
+    pub(crate) description: String,
+    pub(crate) rating: String, // "G", "PG", "PG-13", "R", or "Inappropriate"
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Clone)]
 struct FrameInfo {
     frame: String,
     status: String,
     moderation: ModerationResult,
 }
 
+/// A representative frame plus the near-identical frames it stood in for.
+struct FrameGroup {
+    representative: FrameInfo,
+    duplicates: Vec<FrameInfo>,
+}
+
+fn dedup_hamming_threshold() -> u32 {
+    env::var("DEDUP_HAMMING_THRESHOLD")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(5)
+}
+
+/// Walks frames in order, folding any frame whose dHash is fewer than
+/// `DEDUP_HAMMING_THRESHOLD` bits away from the last emitted frame into that
+/// frame's group instead of giving it its own moderation call.
+fn dedupe_frames(frames: Vec<FrameInfo>) -> Vec<FrameGroup> {
+    let threshold = dedup_hamming_threshold();
+    let mut groups: Vec<FrameGroup> = Vec::new();
+    let mut last_emitted_hash: Option<u64> = None;
+
+    for frame in frames {
+        let hash = dhash::compute(&frame.frame);
+        let is_duplicate = match (hash, last_emitted_hash) {
+            (Some(h), Some(last)) => dhash::hamming_distance(h, last) < threshold,
+            _ => false,
+        };
+
+        if is_duplicate {
+            groups
+                .last_mut()
+                .expect("a duplicate always follows an emitted representative")
+                .duplicates
+                .push(frame);
+        } else {
+            if let Some(h) = hash {
+                last_emitted_hash = Some(h);
+            }
+            groups.push(FrameGroup {
+                representative: frame,
+                duplicates: Vec::new(),
+            });
+        }
+    }
+
+    groups
+}
+
+/// Orders ratings from least to most severe so callers can compute a worst-of
+/// verdict and gate `FAIL_FAST` on a threshold. Unrecognized ratings (e.g.
+/// from a parsing error) are treated as the most severe, since we can't
+/// vouch for them.
+fn rating_rank(rating: &str) -> u8 {
+    match rating {
+        "G" => 0,
+        "PG" => 1,
+        "PG-13" => 2,
+        "R" => 3,
+        _ => 4, // "Inappropriate" and anything unrecognized
+    }
+}
+
+/// Marks a dedup group's frames as `not_evaluated` without calling the
+/// moderation backend, for use once `FAIL_FAST` has tripped.
+fn not_evaluated_group(group: FrameGroup) -> Vec<FrameInfo> {
+    let blank = ModerationResult {
+        description: String::new(),
+        rating: String::new(),
+    };
+    let mut output = Vec::with_capacity(1 + group.duplicates.len());
+    output.push(FrameInfo {
+        status: "not_evaluated".to_string(),
+        moderation: blank.clone(),
+        ..group.representative
+    });
+    for duplicate in group.duplicates {
+        output.push(FrameInfo {
+            status: "not_evaluated".to_string(),
+            moderation: blank.clone(),
+            ..duplicate
+        });
+    }
+    output
+}
+
+/// Summarizes a response's worst rating (with the frame that produced it)
+/// and a count per rating, so a caller can make an accept/reject decision
+/// without walking the per-frame array itself.
+fn aggregate_summary(frames: &[FrameInfo]) -> serde_json::Value {
+    let mut counts: std::collections::HashMap<&str, u32> = std::collections::HashMap::new();
+    let mut worst: Option<&FrameInfo> = None;
+
+    for frame in frames {
+        if frame.status == "not_evaluated" {
+            continue;
+        }
+        *counts.entry(frame.moderation.rating.as_str()).or_insert(0) += 1;
+        let is_new_worst = match worst {
+            None => true,
+            Some(w) => rating_rank(&frame.moderation.rating) > rating_rank(&w.moderation.rating),
+        };
+        if is_new_worst {
+            worst = Some(frame);
+        }
+    }
+
+    json!({
+        "worst_rating": worst.map(|f| f.moderation.rating.clone()),
+        "worst_frame": worst.and_then(|f| extract_frame_number(&f.frame)),
+        "counts": counts,
+    })
+}
+
 fn extract_frame_number(path: &str) -> Option<u32> {
     let file_stem = Path::new(path).file_stem()?.to_string_lossy();
     let parts: Vec<&str> = file_stem.split('_').collect();
@@ -42,78 +166,135 @@ fn extract_frame_number(path: &str) -> Option<u32> {
     parts[1].parse().ok()
 }
 
-async fn get_frame_moderation(frame_path: &str) -> Result<ModerationResult, Box<dyn std::error::Error>> {
-    let api_key = env::var("GROK_API_KEY")?;
-    let file_bytes = tokio::fs::read(frame_path).await?;
-    let base64_image = general_purpose::STANDARD.encode(&file_bytes);
-    let data_url = format!("data:image/jpeg;base64,{}", base64_image);
-
-    let messages = vec![
-        json!({
-            "role": "system",
-            "content": "You are an image moderator. Analyze the image and return a JSON object with exactly two fields: 'description' (a concise analysis) and 'rating' (one of 'G', 'PG', 'PG-13', 'R', or 'Inappropriate'). Your response must be strictly valid JSON without any additional text."
-        }),
-        json!({
-            "role": "user",
-            "content": [{
-                "type": "image_url",
-                "image_url": {
-                    "url": data_url,
-                    "detail": "high"
-                }
-            }]
-        }),
-    ];
-
-    let payload = json!({
-        "model": "grok-2-vision-latest",
-        "messages": messages,
-        "temperature": 0.7
-    });
+/// A `process_video` failure, carrying a machine-readable `reason` tag
+/// alongside the human-readable message so `/jobs/{id}` can report a
+/// distinguishable status (e.g. an actual 413) instead of collapsing every
+/// failure into the same opaque string.
+pub(crate) struct ProcessError {
+    pub(crate) status: u16,
+    pub(crate) reason: &'static str,
+    pub(crate) message: String,
+}
 
-    println!("Grok API Payload:\n{}", serde_json::to_string_pretty(&payload)?);
+impl ProcessError {
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self { status: 400, reason: "bad_request", message: message.into() }
+    }
 
-    let client = reqwest::Client::new();
-    let resp = client
-        .post("https://api.x.ai/v1/chat/completions")
-        .header("Authorization", format!("Bearer {}", api_key))
-        .json(&payload)
-        .send()
-        .await?;
-
-    let json_resp: serde_json::Value = resp.json().await?;
-    println!("Grok API JSON response:\n{}", serde_json::to_string_pretty(&json_resp)?);
-
-    let content = json_resp["choices"][0]["message"]["content"]
-        .as_str()
-        .unwrap_or("")
-        .to_string();
-
-    let cleaned = if content.starts_with("```") {
-        content
-            .trim_start_matches("```json")
-            .trim_start_matches("```")
-            .trim_end_matches("```")
-            .trim()
-            .to_string()
-    } else {
-        content.trim().to_string()
+    fn payload_too_large(message: impl Into<String>) -> Self {
+        Self { status: 413, reason: "payload_too_large", message: message.into() }
+    }
+
+    fn internal(message: impl Into<String>) -> Self {
+        Self { status: 500, reason: "internal_error", message: message.into() }
+    }
+}
+
+impl From<ProcessError> for jobs::JobError {
+    fn from(err: ProcessError) -> Self {
+        jobs::JobError {
+            status: err.status,
+            reason: err.reason,
+            message: err.message,
+        }
+    }
+}
+
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(300);
+const DEFAULT_MAX_VIDEO_BYTES: u64 = 500 * 1024 * 1024;
+
+fn max_video_bytes() -> u64 {
+    env::var("MAX_VIDEO_BYTES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_MAX_VIDEO_BYTES)
+}
+
+const SCALE_EXPR: &str = r"scale=w='if(gt(iw,ih),300,-2)':h='if(gt(iw,ih),-2,300)'";
+
+fn scene_threshold() -> f64 {
+    env::var("SCENE_THRESHOLD")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0.4)
+}
+
+/// Runs ffmpeg's frame extraction, using scene-change detection when
+/// `SELECTION_MODE=scene` so static stretches of video don't burn an API call
+/// per second. Falls back to the blind `fps=1` sampling when scene detection
+/// finds nothing (very short or static clips).
+fn extract_frames(input: &Path, output_pattern: &str) -> std::io::Result<std::process::ExitStatus> {
+    let scene_mode = env::var("SELECTION_MODE").unwrap_or_default() == "scene";
+
+    let run = |filter: String, vsync_vfr: bool| {
+        let mut args = vec!["-y".to_string(), "-nostdin".to_string()];
+        args.push("-i".to_string());
+        args.push(input.to_string_lossy().into_owned());
+        args.push("-vf".to_string());
+        args.push(filter);
+        if vsync_vfr {
+            args.push("-vsync".to_string());
+            args.push("vfr".to_string());
+        }
+        args.push(output_pattern.to_string());
+        Command::new("ffmpeg").args(&args).status()
     };
 
-    println!("Cleaned moderation response:\n{}", cleaned);
+    let output_dir = Path::new(output_pattern)
+        .parent()
+        .expect("output pattern always has a parent dir");
 
-    let moderation: ModerationResult = serde_json::from_str(&cleaned)
-        .unwrap_or(ModerationResult {
-            description: format!("Parsing error in response: {}", cleaned),
-            rating: "Inappropriate".to_string(),
-        });
+    if scene_mode {
+        let filter = format!("select='gt(scene,{})',{}", scene_threshold(), SCALE_EXPR);
+        let status = run(filter, true)?;
+        let produced_frames = stdfs::read_dir(output_dir)
+            .map(|entries| entries.flatten().any(|e| e.path().extension().map_or(false, |ext| ext == "jpg")))
+            .unwrap_or(false);
+        if status.success() && produced_frames {
+            return Ok(status);
+        }
+
+        // The scene pass may have written some frames before failing or
+        // coming up empty; clear them so the fps=1 fallback's own numbering
+        // isn't mixed with stale leftovers.
+        clear_jpg_frames(output_dir);
+    }
 
-    Ok(moderation)
+    run(format!("fps=1,{}", SCALE_EXPR), false)
 }
 
-async fn get_frame_moderation_with_retry(frame_path: &str, retries: u32, delay_ms: u64) -> ModerationResult {
+fn clear_jpg_frames(dir: &Path) {
+    if let Ok(entries) = stdfs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().map_or(false, |ext| ext == "jpg") {
+                let _ = stdfs::remove_file(&path);
+            }
+        }
+    }
+}
+
+fn moderation_concurrency() -> usize {
+    env::var("MODERATION_CONCURRENCY")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(4)
+}
+
+async fn get_frame_moderation_with_retry(
+    backend: &dyn ModerationBackend,
+    frame_path: &str,
+    retries: u32,
+    delay_ms: u64,
+) -> ModerationResult {
     for attempt in 0..=retries {
-        match get_frame_moderation(frame_path).await {
+        let outcome = async {
+            let frame_bytes = tokio::fs::read(frame_path).await?;
+            backend.moderate(&frame_bytes).await
+        }
+        .await;
+        match outcome {
             Ok(moderation) => return moderation,
             Err(e) => {
                 if attempt < retries {
@@ -133,46 +314,59 @@ async fn get_frame_moderation_with_retry(frame_path: &str, retries: u32, delay_m
     }
 }
 
-#[post("/moderate")]
-async fn moderate(req: HttpRequest, body: web::Json<RequestBody>) -> impl Responder {
-    let expected_token = env::var("ZIPSTORY_TOKEN").unwrap_or_default();
-    let token = req.headers().get("zipstory-token").and_then(|v| v.to_str().ok());
-    if token != Some(expected_token.as_str()) {
-        return HttpResponse::Unauthorized().finish();
-    }
-
-    let parsed_url = match Url::parse(&body.url) {
-        Ok(url) => url,
-        Err(e) => return HttpResponse::BadRequest().body(format!("Invalid URL: {}", e)),
-    };
+/// Downloads the video at `url`, extracts frames with ffmpeg, and moderates
+/// them concurrently. Used by both the queue worker and (indirectly) the
+/// `/moderate` handler that enqueues work for it.
+async fn process_video(backend: &dyn ModerationBackend, url: &str) -> Result<serde_json::Value, ProcessError> {
+    let parsed_url = Url::parse(url).map_err(|e| ProcessError::bad_request(format!("Invalid URL: {}", e)))?;
 
-    let response = match reqwest::get(parsed_url.clone()).await {
-        Ok(resp) => resp,
-        Err(e) => return HttpResponse::BadRequest().body(format!("Error downloading URL: {}", e)),
-    };
+    let mut response = reqwest::Client::new()
+        .get(parsed_url.clone())
+        .timeout(DOWNLOAD_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| ProcessError::bad_request(format!("Error downloading URL: {}", e)))?;
 
     if !response.status().is_success() {
-        return HttpResponse::BadRequest()
-            .body(format!("Failed to download URL. HTTP Status: {}", response.status()));
+        return Err(ProcessError::bad_request(format!(
+            "Failed to download URL. HTTP Status: {}",
+            response.status()
+        )));
     }
 
-    let bytes = match response.bytes().await {
-        Ok(b) => b,
-        Err(e) => return HttpResponse::InternalServerError().body(format!("Error reading response: {}", e)),
-    };
+    let max_bytes = max_video_bytes();
+    if response.content_length().is_some_and(|len| len > max_bytes) {
+        return Err(ProcessError::payload_too_large(format!(
+            "Content-Length exceeds MAX_VIDEO_BYTES ({})",
+            max_bytes
+        )));
+    }
 
-    let mut tmp_file = match NamedTempFile::new() {
-        Ok(file) => file,
-        Err(e) => return HttpResponse::InternalServerError().body(format!("Error creating temp file: {}", e)),
-    };
-    if let Err(e) = tmp_file.write_all(&bytes) {
-        return HttpResponse::InternalServerError().body(format!("Error writing to temp file: {}", e));
+    let mut tmp_file = NamedTempFile::new()
+        .map_err(|e| ProcessError::internal(format!("Error creating temp file: {}", e)))?;
+
+    // Stream the body straight into the temp file instead of buffering the
+    // whole video in memory, enforcing MAX_VIDEO_BYTES as we go.
+    let mut downloaded: u64 = 0;
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| ProcessError::internal(format!("Error reading response: {}", e)))?
+    {
+        downloaded += chunk.len() as u64;
+        if downloaded > max_bytes {
+            return Err(ProcessError::payload_too_large(format!(
+                "download exceeded MAX_VIDEO_BYTES ({})",
+                max_bytes
+            )));
+        }
+        tmp_file
+            .write_all(&chunk)
+            .map_err(|e| ProcessError::internal(format!("Error writing to temp file: {}", e)))?;
     }
 
-    let temp_dir = match TempDir::new() {
-        Ok(dir) => dir,
-        Err(e) => return HttpResponse::InternalServerError().body(format!("Error creating temp dir: {}", e)),
-    };
+    let temp_dir =
+        TempDir::new().map_err(|e| ProcessError::internal(format!("Error creating temp dir: {}", e)))?;
 
     let debug_mode = env::var("DEBUG").unwrap_or_default().to_lowercase() == "true";
     let thumbnails_dir = PathBuf::from("./thumbnails");
@@ -180,20 +374,8 @@ async fn moderate(req: HttpRequest, body: web::Json<RequestBody>) -> impl Respon
         let _ = stdfs::create_dir_all(&thumbnails_dir);
     }
 
-    let filter = r"fps=1,scale=w='if(gt(iw,ih),300,-2)':h='if(gt(iw,ih),-2,300)'";
     let output_pattern = format!("{}/frame_%03d.jpg", temp_dir.path().to_string_lossy());
-
-    let ffmpeg_status = Command::new("ffmpeg")
-        .args([
-            "-y",
-            "-nostdin",
-            "-i",
-            tmp_file.path().to_str().unwrap(),
-            "-vf",
-            filter,
-            &output_pattern,
-        ])
-        .status();
+    let ffmpeg_status = extract_frames(tmp_file.path(), &output_pattern);
 
     match ffmpeg_status {
         Ok(status) if status.success() => {
@@ -227,31 +409,240 @@ async fn moderate(req: HttpRequest, body: web::Json<RequestBody>) -> impl Respon
 
             frames.sort_by_key(|f| extract_frame_number(&f.frame).unwrap_or(0));
 
-            // Sequentially moderate frames with a delay between requests.
-            let mut moderated_frames = Vec::new();
-            for frame in frames {
-                let moderation = get_frame_moderation_with_retry(&frame.frame, 3, 200).await;
-                let mut moderated_frame = frame;
-                moderated_frame.moderation = moderation;
-                moderated_frames.push(moderated_frame);
-                sleep(Duration::from_millis(200)).await;
-            }
+            // Moderate frames concurrently, bounded by a semaphore so we don't
+            // hammer the rate-limited vision endpoint.
+            let semaphore = Arc::new(Semaphore::new(moderation_concurrency()));
+            // Skip near-identical frames: each group only costs one
+            // moderation call, and its duplicates inherit the result.
+            let groups = dedupe_frames(frames);
+
+            let fail_fast = env::var("FAIL_FAST").unwrap_or_default().to_lowercase() == "true";
+            let disqualifying_rank = rating_rank(
+                &env::var("DISQUALIFYING_RATING").unwrap_or_else(|_| "Inappropriate".to_string()),
+            );
+            let disqualified = Arc::new(AtomicBool::new(false));
+
+            let tasks = groups.into_iter().map(|group| {
+                let semaphore = Arc::clone(&semaphore);
+                let disqualified = Arc::clone(&disqualified);
+                async move {
+                    if fail_fast && disqualified.load(Ordering::Relaxed) {
+                        return not_evaluated_group(group);
+                    }
+                    let _permit = semaphore.acquire_owned().await.expect("semaphore closed");
+                    if fail_fast && disqualified.load(Ordering::Relaxed) {
+                        return not_evaluated_group(group);
+                    }
+
+                    let moderation =
+                        get_frame_moderation_with_retry(backend, &group.representative.frame, 3, 200).await;
+                    if fail_fast && rating_rank(&moderation.rating) >= disqualifying_rank {
+                        disqualified.store(true, Ordering::Relaxed);
+                    }
+
+                    let mut output = Vec::with_capacity(1 + group.duplicates.len());
+                    output.push(FrameInfo {
+                        moderation: moderation.clone(),
+                        ..group.representative
+                    });
+                    for duplicate in group.duplicates {
+                        output.push(FrameInfo {
+                            status: "deduplicated".to_string(),
+                            moderation: moderation.clone(),
+                            ..duplicate
+                        });
+                    }
+                    output
+                }
+            });
+            let mut moderated_frames: Vec<FrameInfo> =
+                join_all(tasks).await.into_iter().flatten().collect();
+            moderated_frames.sort_by_key(|f| extract_frame_number(&f.frame).unwrap_or(0));
 
-            HttpResponse::Ok().json(json!({
-                "message": "File processed successfully - all frames moderated sequentially",
-                "frames": moderated_frames,
-            }))
+            let summary = aggregate_summary(&moderated_frames);
+            Ok(json!({ "frames": moderated_frames, "summary": summary }))
         }
-        Ok(status) => HttpResponse::InternalServerError().body(format!("ffmpeg failed with status: {}", status)),
-        Err(e) => HttpResponse::InternalServerError().body(format!("Failed to execute ffmpeg: {}", e)),
+        Ok(status) => Err(ProcessError::internal(format!("ffmpeg failed with status: {}", status))),
+        Err(e) => Err(ProcessError::internal(format!("Failed to execute ffmpeg: {}", e))),
+    }
+}
+
+/// Pulls `(job_id, url)` pairs off the queue and runs them one at a time,
+/// recording progress in the shared job store as it goes.
+async fn run_worker(
+    backend: Box<dyn ModerationBackend>,
+    queue: web::Data<JobQueue>,
+    mut receiver: mpsc::Receiver<(Uuid, String)>,
+) {
+    while let Some((job_id, url)) = receiver.recv().await {
+        queue.mark_running(job_id);
+        match process_video(backend.as_ref(), &url).await {
+            Ok(frames) => queue.mark_done(job_id, frames),
+            Err(e) => queue.mark_failed(job_id, e.into()),
+        }
+    }
+}
+
+#[post("/moderate")]
+async fn moderate(req: HttpRequest, body: web::Json<RequestBody>, queue: web::Data<JobQueue>) -> impl Responder {
+    let expected_token = env::var("ZIPSTORY_TOKEN").unwrap_or_default();
+    let token = req.headers().get("zipstory-token").and_then(|v| v.to_str().ok());
+    if token != Some(expected_token.as_str()) {
+        return HttpResponse::Unauthorized().finish();
+    }
+
+    if let Err(e) = Url::parse(&body.url) {
+        return HttpResponse::BadRequest().body(format!("Invalid URL: {}", e));
+    }
+
+    let job_id = match queue.enqueue(body.url.clone()) {
+        Some(job_id) => job_id,
+        None => {
+            return HttpResponse::ServiceUnavailable()
+                .body("Moderation queue is full, try again shortly")
+        }
+    };
+    HttpResponse::Accepted().json(json!({ "job_id": job_id }))
+}
+
+#[get("/jobs/{id}")]
+async fn job_status(path: web::Path<Uuid>, queue: web::Data<JobQueue>) -> impl Responder {
+    match queue.get(&path.into_inner()) {
+        Some(view) => {
+            let status = actix_web::http::StatusCode::from_u16(view.http_status())
+                .unwrap_or(actix_web::http::StatusCode::INTERNAL_SERVER_ERROR);
+            HttpResponse::build(status).json(view)
+        }
+        None => HttpResponse::NotFound().finish(),
     }
 }
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     dotenv().ok();
-    HttpServer::new(|| actix_web::App::new().service(moderate))
-        .bind("0.0.0.0:8080")?
-        .run()
-        .await
-}
\ No newline at end of file
+
+    let backend = build_backend().expect("failed to initialize moderation backend");
+
+    let (queue, receiver) = JobQueue::new(100);
+    let queue = web::Data::new(queue);
+
+    tokio::spawn(run_worker(backend, queue.clone(), receiver));
+
+    HttpServer::new(move || {
+        actix_web::App::new()
+            .app_data(queue.clone())
+            .service(moderate)
+            .service(job_status)
+    })
+    .bind("0.0.0.0:8080")?
+    .run()
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::write_gradient;
+
+    fn blank_frame(path: String, status: &str) -> FrameInfo {
+        FrameInfo {
+            frame: path,
+            status: status.to_string(),
+            moderation: ModerationResult {
+                description: String::new(),
+                rating: String::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn dedupe_frames_folds_a_near_identical_frame_into_its_predecessor() {
+        let dir = tempfile::tempdir().unwrap();
+        let frame_one = dir.path().join("frame_001.jpg");
+        let frame_two = dir.path().join("frame_002.jpg");
+        let frame_three = dir.path().join("frame_003.jpg");
+        write_gradient(&frame_one, true);
+        write_gradient(&frame_two, true); // visually identical to frame_one
+        write_gradient(&frame_three, false); // the opposite gradient
+
+        let frames = vec![
+            blank_frame(frame_one.to_string_lossy().into_owned(), "extracted"),
+            blank_frame(frame_two.to_string_lossy().into_owned(), "extracted"),
+            blank_frame(frame_three.to_string_lossy().into_owned(), "extracted"),
+        ];
+
+        let groups = dedupe_frames(frames);
+
+        assert_eq!(groups.len(), 2, "the near-identical frame should fold into one group");
+        assert_eq!(groups[0].duplicates.len(), 1);
+        assert_eq!(groups[0].duplicates[0].frame, frame_two.to_string_lossy());
+        assert!(groups[1].duplicates.is_empty());
+    }
+
+    #[test]
+    fn dedupe_frames_keeps_a_single_frame_as_its_own_group() {
+        let dir = tempfile::tempdir().unwrap();
+        let frame = dir.path().join("frame_001.jpg");
+        write_gradient(&frame, true);
+
+        let groups = dedupe_frames(vec![blank_frame(frame.to_string_lossy().into_owned(), "extracted")]);
+
+        assert_eq!(groups.len(), 1);
+        assert!(groups[0].duplicates.is_empty());
+    }
+
+    #[test]
+    fn rating_rank_orders_from_least_to_most_severe() {
+        assert!(rating_rank("G") < rating_rank("PG"));
+        assert!(rating_rank("PG") < rating_rank("PG-13"));
+        assert!(rating_rank("PG-13") < rating_rank("R"));
+        assert!(rating_rank("R") < rating_rank("Inappropriate"));
+    }
+
+    #[test]
+    fn rating_rank_treats_unrecognized_ratings_as_most_severe() {
+        assert_eq!(rating_rank("not a real rating"), rating_rank("Inappropriate"));
+    }
+
+    fn rated_frame(frame: &str, status: &str, rating: &str) -> FrameInfo {
+        FrameInfo {
+            frame: frame.to_string(),
+            status: status.to_string(),
+            moderation: ModerationResult {
+                description: String::new(),
+                rating: rating.to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn aggregate_summary_picks_the_worst_rating_and_its_frame() {
+        let frames = vec![
+            rated_frame("frame_001.jpg", "extracted", "G"),
+            rated_frame("frame_002.jpg", "extracted", "R"),
+            rated_frame("frame_003.jpg", "extracted", "PG"),
+        ];
+
+        let summary = aggregate_summary(&frames);
+
+        assert_eq!(summary["worst_rating"], "R");
+        assert_eq!(summary["worst_frame"], 2);
+        assert_eq!(summary["counts"]["G"], 1);
+        assert_eq!(summary["counts"]["R"], 1);
+        assert_eq!(summary["counts"]["PG"], 1);
+    }
+
+    #[test]
+    fn aggregate_summary_ignores_not_evaluated_frames() {
+        let frames = vec![
+            rated_frame("frame_001.jpg", "extracted", "G"),
+            rated_frame("frame_002.jpg", "not_evaluated", "Inappropriate"),
+        ];
+
+        let summary = aggregate_summary(&frames);
+
+        assert_eq!(summary["worst_rating"], "G");
+        assert_eq!(summary["worst_frame"], 1);
+        assert!(summary["counts"].get("Inappropriate").is_none());
+    }
+}
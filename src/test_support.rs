@@ -0,0 +1,16 @@
+use image::{Rgb, RgbImage};
+use std::path::Path;
+
+/// A 16x16 horizontal gradient: ascending brightness left-to-right, or
+/// descending. dHash compares each row's adjacent pixels, so an ascending
+/// gradient hashes to all-zero bits and a descending one hashes to all-one
+/// bits, giving tests a predictable maximal difference. Shared by the
+/// `dhash` and top-level test modules so the fixture doesn't drift between
+/// two copies.
+pub(crate) fn write_gradient(path: &Path, ascending: bool) {
+    let img = RgbImage::from_fn(16, 16, |x, _y| {
+        let v = if ascending { (x * 16) as u8 } else { 255 - (x * 16) as u8 };
+        Rgb([v, v, v])
+    });
+    img.save(path).expect("failed to write test image");
+}